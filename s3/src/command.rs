@@ -1,12 +1,279 @@
+//! Request bodies, headers, and signing primitives for every S3 operation
+//! this crate supports, expressed as the [`Command`] enum.
+//!
+//! This module only computes values — bodies, header name/value pairs, the
+//! SigV4 canonical signed-header set — and never sends a request; its only
+//! I/O is [`Command::next_signed_chunk`] reading a `PutObjectStream` body. The
+//! request-building layer (`request.rs`/`bucket.rs`) is the caller: it
+//! matches on `Command`, sends the bytes `content_length`/`sha256`/`headers`
+//! describe, and signs with the names `signed_headers` returns. Those two
+//! files are not part of this checkout, so when a `Command` variant's shape
+//! changes (a unit variant becoming a struct variant, a new field), their
+//! construction sites need the matching mechanical update before the crate
+//! builds as a whole; that update isn't visible from here.
+
 use std::collections::HashMap;
 
 use crate::error::S3Error;
 use crate::serde_types::{
-    BucketLifecycleConfiguration, CompleteMultipartUploadData, CorsConfiguration,
+    BucketLifecycleConfiguration, CompleteMultipartUploadData, CorsConfiguration, ObjectIdentifier,
+    PartChecksum,
 };
 
 use crate::EMPTY_PAYLOAD_SHA;
+use hmac::{Hmac, Mac};
+use md5::Md5;
+use sha1::Sha1;
 use sha2::{Digest, Sha256};
+use std::pin::Pin;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// `x-amz-content-sha256` sentinel for an AWS chunked, incrementally-signed
+/// body, used by [`Command::PutObjectStream`] in place of a real digest.
+const STREAMING_PAYLOAD_SHA: &str = "STREAMING-AWS4-HMAC-SHA256-PAYLOAD";
+
+/// SHA256 digest of `bytes`, the reusable core of [`Command::sha256`] and
+/// of [`ChecksumAlgorithm::Sha256`].
+fn sha256_digest(bytes: &[u8]) -> Vec<u8> {
+    let mut sha = Sha256::default();
+    sha.update(bytes);
+    sha.finalize().to_vec()
+}
+
+/// Serializes a batch delete request as the `<Delete>` XML body S3 expects,
+/// the same quick_xml-based approach `PutBucketLifecycle` uses.
+fn delete_objects_xml(objects: &[ObjectIdentifier], quiet: bool) -> Result<String, S3Error> {
+    #[derive(serde::Serialize)]
+    #[serde(rename = "Delete")]
+    struct DeleteXml<'a> {
+        #[serde(rename = "Object")]
+        object: &'a [ObjectIdentifier],
+        #[serde(rename = "Quiet", skip_serializing_if = "std::ops::Not::not")]
+        quiet: bool,
+    }
+    Ok(quick_xml::se::to_string(&DeleteXml { object: objects, quiet })?)
+}
+
+/// Resolves a [`ContentMd5`] against a lazily-computed body, the shared
+/// logic behind [`Command::content_md5_header`]. `body` is only invoked for
+/// [`ContentMd5::Auto`], so callers can defer serializing a request they
+/// already have to hash elsewhere.
+fn resolve_content_md5(
+    content_md5: &ContentMd5,
+    body: impl FnOnce() -> Result<Vec<u8>, S3Error>,
+) -> Result<Option<String>, S3Error> {
+    use base64::Engine;
+    match content_md5 {
+        ContentMd5::Some(value) => Ok(Some(value.clone())),
+        ContentMd5::None => Ok(None),
+        ContentMd5::Auto => {
+            let mut md5 = Md5::default();
+            md5.update(body()?);
+            Ok(Some(base64::engine::general_purpose::STANDARD.encode(md5.finalize())))
+        }
+    }
+}
+
+/// One entry of a [`DeleteObjectsResult`]'s `<Deleted>` list: a key (and,
+/// for versioned buckets, the version) S3 actually removed.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeletedObject {
+    pub key: String,
+    #[serde(default)]
+    pub version_id: Option<String>,
+    #[serde(default)]
+    pub delete_marker: bool,
+    #[serde(default)]
+    pub delete_marker_version_id: Option<String>,
+}
+
+/// One entry of a [`DeleteObjectsResult`]'s `<Error>` list: a key S3 refused
+/// to delete, with the reason.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteObjectError {
+    pub key: String,
+    #[serde(default)]
+    pub version_id: Option<String>,
+    pub code: String,
+    pub message: String,
+}
+
+/// Parsed `<DeleteResult>` body returned by [`Command::DeleteObjects`]: the
+/// keys that were removed and the keys that failed, key by key rather than
+/// one status per request.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteObjectsResult {
+    #[serde(default, rename = "Deleted")]
+    pub deleted: Vec<DeletedObject>,
+    #[serde(default, rename = "Error")]
+    pub errors: Vec<DeleteObjectError>,
+}
+
+impl DeleteObjectsResult {
+    /// Parses the `<DeleteResult>` XML body S3 returns from a successful
+    /// `POST ?delete` request.
+    pub fn parse(xml: &str) -> Result<Self, S3Error> {
+        Ok(quick_xml::de::from_str(xml)?)
+    }
+}
+
+/// Computes the next chunk's signature in an AWS chunked
+/// `STREAMING-AWS4-HMAC-SHA256-PAYLOAD` upload, chaining from the previous
+/// chunk's signature. The first call is seeded with the request's own
+/// SigV4 signature in place of `previous_signature`.
+pub fn next_chunk_signature(
+    signing_key: &[u8],
+    date: &str,
+    scope: &str,
+    previous_signature: &str,
+    chunk_data: &[u8],
+) -> String {
+    let chunk_sha = hex::encode(sha256_digest(chunk_data));
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256-PAYLOAD\n{date}\n{scope}\n{previous_signature}\n{EMPTY_PAYLOAD_SHA}\n{chunk_sha}"
+    );
+    hex::encode(hmac_sha256(signing_key, string_to_sign.as_bytes()))
+}
+
+/// HMAC-SHA256 of `data` under `key`, the shared primitive behind the
+/// chunk-signing chain in [`next_chunk_signature`] and the SigV4
+/// `dateKey -> regionKey -> serviceKey -> signingKey -> signature` chain in
+/// [`presigned_post_fields`].
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC-SHA256 accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Escapes `value` for embedding in a JSON string literal: backslash and
+/// quote, plus the control characters (`U+0000..=U+001F`) JSON requires be
+/// escaped, via the same `\n`/`\r`/`\t` shorthands `serde_json` emits and
+/// `\u00XX` for the rest.
+fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl PostPolicyCondition {
+    fn to_json(&self) -> String {
+        match self {
+            PostPolicyCondition::ExactMatch { field, value } => {
+                format!("{{\"{}\":\"{}\"}}", json_escape(field), json_escape(value))
+            }
+            PostPolicyCondition::StartsWith { field, value } => format!(
+                "[\"starts-with\",\"${}\",\"{}\"]",
+                json_escape(field),
+                json_escape(value)
+            ),
+            PostPolicyCondition::ContentLengthRange { min, max } => {
+                format!("[\"content-length-range\",{min},{max}]")
+            }
+        }
+    }
+}
+
+/// Builds the policy document and signed form fields for a `PresignPost`
+/// command's browser direct-to-bucket upload. `conditions` and `fields`
+/// come straight from [`Command::PresignPost`]; `datetime` is the SigV4
+/// timestamp (`yyyymmddTHHMMSSZ`) and `expiration` the policy's ISO8601
+/// expiry, both computed by the caller so this function stays a pure,
+/// clock-free transform like the rest of this module. Signing follows the
+/// standard chain: `dateKey = HMAC("AWS4"+secret, yyyymmdd)`,
+/// `regionKey = HMAC(dateKey, region)`, `serviceKey = HMAC(regionKey, "s3")`,
+/// `signingKey = HMAC(serviceKey, "aws4_request")`,
+/// `signature = hex(HMAC(signingKey, base64(policy)))`.
+pub fn presigned_post_fields(
+    bucket: &str,
+    region: &str,
+    access_key: &str,
+    secret_key: &str,
+    security_token: Option<&str>,
+    datetime: &str,
+    expiration: &str,
+    conditions: &[PostPolicyCondition],
+    fields: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    use base64::Engine;
+
+    let date8 = &datetime[..8];
+    let scope = format!("{date8}/{region}/s3/aws4_request");
+    let credential = format!("{access_key}/{scope}");
+
+    let mut condition_entries = vec![format!("{{\"bucket\":\"{}\"}}", json_escape(bucket))];
+    condition_entries.extend(conditions.iter().map(PostPolicyCondition::to_json));
+    condition_entries.push("{\"x-amz-algorithm\":\"AWS4-HMAC-SHA256\"}".to_string());
+    condition_entries.push(format!(
+        "{{\"x-amz-credential\":\"{}\"}}",
+        json_escape(&credential)
+    ));
+    condition_entries.push(format!(
+        "{{\"x-amz-date\":\"{}\"}}",
+        json_escape(datetime)
+    ));
+    for (field, value) in fields {
+        condition_entries.push(format!(
+            "{{\"{}\":\"{}\"}}",
+            json_escape(field),
+            json_escape(value)
+        ));
+    }
+
+    let policy = format!(
+        "{{\"expiration\":\"{}\",\"conditions\":[{}]}}",
+        json_escape(expiration),
+        condition_entries.join(",")
+    );
+    let policy_b64 = base64::engine::general_purpose::STANDARD.encode(policy.as_bytes());
+
+    let date_key = hmac_sha256(format!("AWS4{secret_key}").as_bytes(), date8.as_bytes());
+    let region_key = hmac_sha256(&date_key, region.as_bytes());
+    let service_key = hmac_sha256(&region_key, b"s3");
+    let signing_key = hmac_sha256(&service_key, b"aws4_request");
+    let signature = hex::encode(hmac_sha256(&signing_key, policy_b64.as_bytes()));
+
+    let mut form_fields = fields.clone();
+    form_fields.insert("policy".to_string(), policy_b64);
+    form_fields.insert(
+        "x-amz-algorithm".to_string(),
+        "AWS4-HMAC-SHA256".to_string(),
+    );
+    form_fields.insert("x-amz-credential".to_string(), credential);
+    form_fields.insert("x-amz-date".to_string(), datetime.to_string());
+    form_fields.insert("x-amz-signature".to_string(), signature);
+    if let Some(token) = security_token {
+        form_fields.insert("x-amz-security-token".to_string(), token.to_string());
+    }
+
+    form_fields
+}
+
+/// Frames one chunk of a chunked-signed body as
+/// `<hexlen>;chunk-signature=<sig>\r\n<data>\r\n`. An empty `data` produces
+/// the required final zero-length chunk that closes the stream.
+pub fn frame_chunk(data: &[u8], signature: &str) -> Vec<u8> {
+    let mut framed = format!("{:x};chunk-signature={signature}\r\n", data.len()).into_bytes();
+    framed.extend_from_slice(data);
+    framed.extend_from_slice(b"\r\n");
+    framed
+}
 
 pub enum HttpMethod {
     Delete,
@@ -66,19 +333,221 @@ impl From<&[u8]> for ContentMd5{
         Self::Some(base64::engine::general_purpose::STANDARD.encode(value))
     }
 }
+
+const SSE_C_ALGORITHM: &str = "x-amz-server-side-encryption-customer-algorithm";
+const SSE_C_KEY: &str = "x-amz-server-side-encryption-customer-key";
+const SSE_C_KEY_MD5: &str = "x-amz-server-side-encryption-customer-key-MD5";
+const COPY_SOURCE_SSE_C_ALGORITHM: &str =
+    "x-amz-copy-source-server-side-encryption-customer-algorithm";
+const COPY_SOURCE_SSE_C_KEY: &str = "x-amz-copy-source-server-side-encryption-customer-key";
+const COPY_SOURCE_SSE_C_KEY_MD5: &str =
+    "x-amz-copy-source-server-side-encryption-customer-key-MD5";
+
+/// A customer-provided, 256-bit SSE-C encryption key.
+///
+/// S3 (and compatible backends such as Garage) never stores this key: it is
+/// sent with every request that touches the object and must be supplied
+/// again on every subsequent request, including as the *source* key of a
+/// `CopyObject` when re-keying an already-encrypted object.
+#[derive(Clone, Debug)]
+pub struct SseCustomerKey {
+    key: [u8; 32],
+}
+
+impl SseCustomerKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        SseCustomerKey { key }
+    }
+
+    fn key_and_md5_b64(&self) -> (String, String) {
+        use base64::Engine;
+        let key_b64 = base64::engine::general_purpose::STANDARD.encode(self.key);
+        let mut md5 = Md5::default();
+        md5.update(self.key);
+        let md5_b64 = base64::engine::general_purpose::STANDARD.encode(md5.finalize());
+        (key_b64, md5_b64)
+    }
+
+    /// The `x-amz-server-side-encryption-customer-*` headers for this key.
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let (key_b64, md5_b64) = self.key_and_md5_b64();
+        vec![
+            (SSE_C_ALGORITHM, "AES256".to_string()),
+            (SSE_C_KEY, key_b64),
+            (SSE_C_KEY_MD5, md5_b64),
+        ]
+    }
+
+    /// The `x-amz-copy-source-server-side-encryption-customer-*` headers,
+    /// used when this key decrypts the source object of a `CopyObject`.
+    pub fn copy_source_headers(&self) -> Vec<(&'static str, String)> {
+        let (key_b64, md5_b64) = self.key_and_md5_b64();
+        vec![
+            (COPY_SOURCE_SSE_C_ALGORITHM, "AES256".to_string()),
+            (COPY_SOURCE_SSE_C_KEY, key_b64),
+            (COPY_SOURCE_SSE_C_KEY_MD5, md5_b64),
+        ]
+    }
+}
+
+/// One condition entry in a POST policy document's `conditions` array.
+///
+/// Mirrors the condition shapes S3 (and Garage) accept for browser
+/// direct-to-bucket uploads: an exact-match object, a `starts-with` array,
+/// or the `content-length-range` array.
 #[derive(Clone, Debug)]
+pub enum PostPolicyCondition {
+    ExactMatch { field: String, value: String },
+    StartsWith { field: String, value: String },
+    ContentLengthRange { min: u64, max: u64 },
+}
+
+/// An additional AWS checksum algorithm requested on an upload, verified by
+/// the backend end-to-end (distinct from the SigV4 payload SHA256).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    Crc32,
+    Crc32c,
+    Sha1,
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    fn header_name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "x-amz-checksum-crc32",
+            ChecksumAlgorithm::Crc32c => "x-amz-checksum-crc32c",
+            ChecksumAlgorithm::Sha1 => "x-amz-checksum-sha1",
+            ChecksumAlgorithm::Sha256 => "x-amz-checksum-sha256",
+        }
+    }
+
+    fn sdk_name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Crc32 => "CRC32",
+            ChecksumAlgorithm::Crc32c => "CRC32C",
+            ChecksumAlgorithm::Sha1 => "SHA1",
+            ChecksumAlgorithm::Sha256 => "SHA256",
+        }
+    }
+
+    /// Base64-encoded digest of `content` under this algorithm.
+    fn digest_b64(&self, content: &[u8]) -> String {
+        use base64::Engine;
+        let digest: Vec<u8> = match self {
+            ChecksumAlgorithm::Crc32 => crc32fast::hash(content).to_be_bytes().to_vec(),
+            ChecksumAlgorithm::Crc32c => crc32c::crc32c(content).to_be_bytes().to_vec(),
+            ChecksumAlgorithm::Sha1 => {
+                let mut sha = Sha1::default();
+                sha.update(content);
+                sha.finalize().to_vec()
+            }
+            ChecksumAlgorithm::Sha256 => sha256_digest(content),
+        };
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    }
+
+    /// The `x-amz-checksum-<alg>` and `x-amz-sdk-checksum-algorithm`
+    /// headers for `content` under this algorithm.
+    pub fn headers(&self, content: &[u8]) -> Vec<(&'static str, String)> {
+        vec![
+            (self.header_name(), self.digest_b64(content)),
+            ("x-amz-sdk-checksum-algorithm", self.sdk_name().to_string()),
+        ]
+    }
+
+    /// Starts a running digest under this algorithm, fed chunk by chunk as
+    /// a [`Command::PutObjectStream`] body is framed — the streaming
+    /// counterpart to [`ChecksumAlgorithm::digest_b64`], which needs the
+    /// whole payload in memory up front.
+    pub fn new_state(&self) -> ChecksumState {
+        match self {
+            ChecksumAlgorithm::Crc32 => ChecksumState::Crc32(crc32fast::Hasher::new()),
+            ChecksumAlgorithm::Crc32c => ChecksumState::Crc32c(0),
+            ChecksumAlgorithm::Sha1 => ChecksumState::Sha1(Sha1::default()),
+            ChecksumAlgorithm::Sha256 => ChecksumState::Sha256(Sha256::default()),
+        }
+    }
+}
+
+/// Incremental checksum state for a streamed upload, updated chunk by
+/// chunk alongside [`next_chunk_signature`] instead of hashing a buffer
+/// that was never fully resident.
+pub enum ChecksumState {
+    Crc32(crc32fast::Hasher),
+    Crc32c(u32),
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl ChecksumState {
+    /// Feeds the next chunk of a streamed body into the running digest.
+    pub fn update(&mut self, chunk: &[u8]) {
+        match self {
+            ChecksumState::Crc32(hasher) => hasher.update(chunk),
+            ChecksumState::Crc32c(crc) => *crc = crc32c::crc32c_append(*crc, chunk),
+            ChecksumState::Sha1(sha) => sha.update(chunk),
+            ChecksumState::Sha256(sha) => sha.update(chunk),
+        }
+    }
+
+    /// Finalizes the running digest into the base64 value sent in the
+    /// `x-amz-checksum-<alg>` header and, for multipart uploads, recorded
+    /// per-part for `CompleteMultipartUpload`.
+    pub fn finish_b64(self) -> String {
+        use base64::Engine;
+        let digest: Vec<u8> = match self {
+            ChecksumState::Crc32(hasher) => hasher.finalize().to_be_bytes().to_vec(),
+            ChecksumState::Crc32c(crc) => crc.to_be_bytes().to_vec(),
+            ChecksumState::Sha1(sha) => sha.finalize().to_vec(),
+            ChecksumState::Sha256(sha) => sha.finalize().to_vec(),
+        };
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    }
+}
+
+/// A [`Command::PutObjectStream`] body. Wraps the trait object so `Command`
+/// can derive `Debug` even though an open stream can't implement it
+/// itself; a stream can't be cloned either, so `Command` derives `Debug`
+/// only, not `Clone`.
+pub struct StreamBody<'a>(pub Pin<Box<dyn AsyncRead + Send + 'a>>);
+
+impl fmt::Debug for StreamBody<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StreamBody(..)")
+    }
+}
+
+#[derive(Debug)]
 pub enum Command<'a> {
-    HeadObject,
+    HeadObject {
+        sse_customer_key: Option<SseCustomerKey>,
+    },
     CopyObject {
         from: &'a str,
+        sse_customer_key: Option<SseCustomerKey>,
+        copy_source_sse_customer_key: Option<SseCustomerKey>,
     },
     DeleteObject,
     DeleteObjectTagging,
-    GetObject,
+    /// Deletes up to 1000 objects in a single `POST ?delete` request,
+    /// instead of one `DeleteObject` round-trip per key. S3/Garage reject
+    /// this request without a `Content-MD5` header; `content_md5` defaults
+    /// to `ContentMd5::Auto`, which [`Command::content_md5_header`]
+    /// resolves by hashing the serialized `<Delete>` body.
+    DeleteObjects {
+        objects: Vec<ObjectIdentifier>,
+        quiet: bool,
+        content_md5: ContentMd5,
+    },
+    GetObject {
+        sse_customer_key: Option<SseCustomerKey>,
+    },
     GetObjectTorrent,
     GetObjectRange {
         start: u64,
         end: Option<u64>,
+        sse_customer_key: Option<SseCustomerKey>,
     },
     GetObjectTagging,
     PutObject {
@@ -88,6 +557,26 @@ pub enum Command<'a> {
         multipart: Option<Multipart<'a>>,
         cache_control: Option<&'a str>,
         content_disposition: Option<&'a str>,
+        sse_customer_key: Option<SseCustomerKey>,
+        checksum_algorithm: Option<ChecksumAlgorithm>,
+    },
+    /// Like `PutObject`, but for a body too large to buffer in memory:
+    /// `body` is read and framed incrementally instead of being handed over
+    /// as one `&[u8]`. `sha256()` returns the `STREAMING-AWS4-HMAC-SHA256-PAYLOAD`
+    /// sentinel for this variant, and `decoded_content_length` backs the
+    /// paired `x-amz-decoded-content-length` header; the caller drives the
+    /// stream with [`Command::next_signed_chunk`], which frames each chunk
+    /// via [`frame_chunk`], signs it via [`next_chunk_signature`], and folds
+    /// it into a running [`ChecksumState`] when one was requested.
+    PutObjectStream {
+        content_type: &'a str,
+        decoded_content_length: u64,
+        body: StreamBody<'a>,
+        multipart: Option<Multipart<'a>>,
+        cache_control: Option<&'a str>,
+        content_disposition: Option<&'a str>,
+        sse_customer_key: Option<SseCustomerKey>,
+        checksum_algorithm: Option<ChecksumAlgorithm>,
     },
     PutObjectTagging {
         tags: &'a str,
@@ -124,6 +613,15 @@ pub enum Command<'a> {
     PresignDelete {
         expiry_secs: u32,
     },
+    /// Produces the policy document and signed form fields for a browser
+    /// direct-to-bucket upload (the POST Object / post_object path), via
+    /// [`presigned_post_fields`]. `fields` are extra caller-supplied form
+    /// fields echoed back verbatim alongside the generated ones.
+    PresignPost {
+        expiry_secs: u32,
+        conditions: Vec<PostPolicyCondition>,
+        fields: HashMap<String, String>,
+    },
     InitiateMultipartUpload {
         content_type: &'a str,
     },
@@ -132,6 +630,18 @@ pub enum Command<'a> {
         content: &'a [u8],
         content_md5: ContentMd5,
         upload_id: &'a str,
+        sse_customer_key: Option<SseCustomerKey>,
+        checksum_algorithm: Option<ChecksumAlgorithm>,
+    },
+    /// Copies a byte range of an existing object into one part of an
+    /// in-progress multipart upload, entirely server-side. Query string is
+    /// the same `?partNumber=&uploadId=` pair as `UploadPart`, built via
+    /// `Multipart::query_string`.
+    UploadPartCopy {
+        from: &'a str,
+        part_number: u32,
+        upload_id: &'a str,
+        copy_source_range: Option<(u64, Option<u64>)>,
     },
     AbortMultipartUpload {
         upload_id: &'a str,
@@ -160,7 +670,7 @@ pub enum Command<'a> {
 impl<'a> Command<'a> {
     pub fn http_verb(&self) -> HttpMethod {
         match *self {
-            Command::GetObject
+            Command::GetObject { .. }
             | Command::GetObjectTorrent
             | Command::GetObjectRange { .. }
             | Command::ListBuckets
@@ -172,10 +682,12 @@ impl<'a> Command<'a> {
             | Command::ListMultipartUploads { .. }
             | Command::PresignGet { .. } => HttpMethod::Get,
             Command::PutObject { .. }
-            | Command::CopyObject { from: _ }
+            | Command::PutObjectStream { .. }
+            | Command::CopyObject { .. }
             | Command::PutObjectTagging { .. }
             | Command::PresignPut { .. }
             | Command::UploadPart { .. }
+            | Command::UploadPartCopy { .. }
             | Command::PutBucketCors { .. }
             | Command::CreateBucket { .. }
             | Command::PutBucketLifecycle { .. } => HttpMethod::Put,
@@ -185,16 +697,17 @@ impl<'a> Command<'a> {
             | Command::PresignDelete { .. }
             | Command::DeleteBucket
             | Command::DeleteBucketLifecycle => HttpMethod::Delete,
-            Command::InitiateMultipartUpload { .. } | Command::CompleteMultipartUpload { .. } => {
-                HttpMethod::Post
-            }
-            Command::HeadObject => HttpMethod::Head,
+            Command::InitiateMultipartUpload { .. }
+            | Command::CompleteMultipartUpload { .. }
+            | Command::PresignPost { .. }
+            | Command::DeleteObjects { .. } => HttpMethod::Post,
+            Command::HeadObject { .. } => HttpMethod::Head,
         }
     }
 
     pub fn content_length(&self) -> Result<usize, S3Error> {
         let result = match &self {
-            Command::CopyObject { from: _ } => 0,
+            Command::CopyObject { .. } => 0,
             Command::PutObject { content, .. } => content.len(),
             Command::PutObjectTagging { tags } => tags.len(),
             Command::UploadPart { content, .. } => content.len(),
@@ -209,6 +722,9 @@ impl<'a> Command<'a> {
             Command::PutBucketLifecycle { configuration } => {
                 quick_xml::se::to_string(configuration)?.as_bytes().len()
             }
+            Command::DeleteObjects { objects, quiet, .. } => {
+                delete_objects_xml(objects, *quiet)?.as_bytes().len()
+            }
             _ => 0,
         };
         Ok(result)
@@ -217,42 +733,332 @@ impl<'a> Command<'a> {
     pub fn content_type(&self) -> String {
         match self {
             Command::InitiateMultipartUpload { content_type } => content_type.to_string(),
-            Command::PutObject { content_type, .. } => content_type.to_string(),
-            Command::CompleteMultipartUpload { .. } | Command::PutBucketLifecycle { .. } => {
-                "application/xml".into()
-            }
+            Command::PutObject { content_type, .. }
+            | Command::PutObjectStream { content_type, .. } => content_type.to_string(),
+            Command::CompleteMultipartUpload { .. }
+            | Command::PutBucketLifecycle { .. }
+            | Command::DeleteObjects { .. } => "application/xml".into(),
             _ => "text/plain".into(),
         }
     }
 
     pub fn sha256(&self) -> Result<String, S3Error> {
         let result = match &self {
-            Command::PutObject { content, .. } => {
-                let mut sha = Sha256::default();
-                sha.update(content);
-                hex::encode(sha.finalize().as_slice())
-            }
-            Command::PutObjectTagging { tags } => {
-                let mut sha = Sha256::default();
-                sha.update(tags.as_bytes());
-                hex::encode(sha.finalize().as_slice())
-            }
+            Command::PutObject { content, .. } => hex::encode(sha256_digest(content)),
+            Command::PutObjectTagging { tags } => hex::encode(sha256_digest(tags.as_bytes())),
             Command::CompleteMultipartUpload { data, .. } => {
-                let mut sha = Sha256::default();
-                sha.update(data.to_string().as_bytes());
-                hex::encode(sha.finalize().as_slice())
+                hex::encode(sha256_digest(data.to_string().as_bytes()))
             }
             Command::CreateBucket { config } => {
                 if let Some(payload) = config.location_constraint_payload() {
-                    let mut sha = Sha256::default();
-                    sha.update(payload.as_bytes());
-                    hex::encode(sha.finalize().as_slice())
+                    hex::encode(sha256_digest(payload.as_bytes()))
                 } else {
                     EMPTY_PAYLOAD_SHA.into()
                 }
             }
+            Command::DeleteObjects { objects, quiet, .. } => {
+                hex::encode(sha256_digest(delete_objects_xml(objects, *quiet)?.as_bytes()))
+            }
+            Command::PutObjectStream { .. } => STREAMING_PAYLOAD_SHA.to_string(),
             _ => EMPTY_PAYLOAD_SHA.into(),
         };
         Ok(result)
     }
+
+    /// The `Content-MD5` header this command must send, if any. S3/Garage
+    /// reject a `POST ?delete` [`Command::DeleteObjects`] request without
+    /// one; `ContentMd5::Auto` resolves it by hashing the same serialized
+    /// `<Delete>` body that [`Command::sha256`] and [`Command::content_length`]
+    /// already compute it over.
+    pub fn content_md5_header(&self) -> Result<Option<String>, S3Error> {
+        match self {
+            Command::DeleteObjects { objects, quiet, content_md5 } => resolve_content_md5(
+                content_md5,
+                || Ok(delete_objects_xml(objects, *quiet)?.into_bytes()),
+            ),
+            _ => Ok(None),
+        }
+    }
+
+    /// Dispatches a [`Command::PresignPost`] to [`presigned_post_fields`],
+    /// the entry point the request layer calls for the POST Object /
+    /// post_object path. `datetime`/`expiration` are still supplied by the
+    /// caller rather than computed here, the same division of labor as
+    /// `PresignGet`/`PresignPut`/`PresignDelete`: this module stays
+    /// clock-free, and whatever calls it owns the wall clock.
+    /// Returns `None` for every other variant.
+    pub fn presign_post_fields(
+        &self,
+        bucket: &str,
+        region: &str,
+        access_key: &str,
+        secret_key: &str,
+        security_token: Option<&str>,
+        datetime: &str,
+        expiration: &str,
+    ) -> Option<HashMap<String, String>> {
+        match self {
+            Command::PresignPost {
+                conditions, fields, ..
+            } => Some(presigned_post_fields(
+                bucket,
+                region,
+                access_key,
+                secret_key,
+                security_token,
+                datetime,
+                expiration,
+                conditions,
+                fields,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Every extra header this command needs on top of the standard ones
+    /// (`Host`, `Date`/`x-amz-date`, `x-amz-content-sha256`, ...): SSE-C,
+    /// checksum, copy-source, and streaming-length headers alike. The
+    /// request-building layer must add all of these to the outgoing request
+    /// *and* fold their names into the SigV4 canonical signed-header set —
+    /// none of them are valid unless they're signed.
+    pub fn extra_headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = self.sse_customer_headers();
+        headers.extend(self.copy_source_headers());
+        headers.extend(self.checksum_headers());
+        headers.extend(self.decoded_content_length_header());
+        headers
+    }
+
+    /// Every header this command's request must carry beyond the standard
+    /// SigV4 ones: [`Command::extra_headers`] plus `Content-MD5` when
+    /// [`Command::content_md5_header`] resolves one. This is the single
+    /// entry point the request layer should call before signing — it's the
+    /// complete set [`Command::signed_headers`] folds into the canonical
+    /// signed-header set.
+    pub fn headers(&self) -> Result<Vec<(&'static str, String)>, S3Error> {
+        let mut headers = self.extra_headers();
+        if let Some(content_md5) = self.content_md5_header()? {
+            headers.push(("Content-MD5", content_md5));
+        }
+        Ok(headers)
+    }
+
+    /// The SigV4 canonical signed-header set for this command: `standard`
+    /// (the headers every request signs regardless of command, e.g. `host`,
+    /// `x-amz-date`, `x-amz-content-sha256`) plus the names [`Command::headers`]
+    /// contributes, lowercased, sorted, and semicolon-joined the way SigV4
+    /// requires. The signing layer builds the canonical request string by
+    /// pairing this with the matching header values from [`Command::headers`].
+    pub fn signed_headers(&self, standard: &[&str]) -> Result<String, S3Error> {
+        let mut names: Vec<String> = standard.iter().map(|name| name.to_lowercase()).collect();
+        names.extend(self.headers()?.iter().map(|(name, _)| name.to_lowercase()));
+        names.sort_unstable();
+        names.dedup();
+        Ok(names.join(";"))
+    }
+
+    /// SSE-C headers that must be added to the request and included in the
+    /// SigV4 signed-header set for this command, if a customer key was set.
+    fn sse_customer_headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Command::PutObject {
+                sse_customer_key, ..
+            }
+            | Command::PutObjectStream {
+                sse_customer_key, ..
+            }
+            | Command::GetObject { sse_customer_key }
+            | Command::GetObjectRange {
+                sse_customer_key, ..
+            }
+            | Command::HeadObject { sse_customer_key }
+            | Command::UploadPart {
+                sse_customer_key, ..
+            } => sse_customer_key
+                .as_ref()
+                .map(SseCustomerKey::headers)
+                .unwrap_or_default(),
+            Command::CopyObject {
+                sse_customer_key,
+                copy_source_sse_customer_key,
+                ..
+            } => {
+                let mut headers = sse_customer_key
+                    .as_ref()
+                    .map(SseCustomerKey::headers)
+                    .unwrap_or_default();
+                if let Some(key) = copy_source_sse_customer_key {
+                    headers.extend(key.copy_source_headers());
+                }
+                headers
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The `?partNumber=&uploadId=` query string this command's request
+    /// must be sent to, if it targets one part of an in-progress multipart
+    /// upload — built via [`Multipart::query_string`] in every case,
+    /// including `UploadPartCopy`, rather than hand-formatting the pair
+    /// again per variant.
+    pub fn multipart_query_string(&self) -> Option<String> {
+        match self {
+            Command::PutObject {
+                multipart: Some(multipart),
+                ..
+            }
+            | Command::PutObjectStream {
+                multipart: Some(multipart),
+                ..
+            } => Some(multipart.query_string()),
+            Command::UploadPart {
+                part_number,
+                upload_id,
+                ..
+            }
+            | Command::UploadPartCopy {
+                part_number,
+                upload_id,
+                ..
+            } => Some(Multipart::new(*part_number, upload_id).query_string()),
+            _ => None,
+        }
+    }
+
+    /// The `x-amz-copy-source` header (and `x-amz-copy-source-range` when a
+    /// range is given) for an `UploadPartCopy` command.
+    fn copy_source_headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Command::UploadPartCopy {
+                from,
+                copy_source_range,
+                ..
+            } => {
+                let mut headers = vec![("x-amz-copy-source", format!("/{from}"))];
+                if let Some((start, end)) = copy_source_range {
+                    let range = match end {
+                        Some(end) => format!("bytes={start}-{end}"),
+                        None => format!("bytes={start}-"),
+                    };
+                    headers.push(("x-amz-copy-source-range", range));
+                }
+                headers
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// `x-amz-checksum-<alg>` and `x-amz-sdk-checksum-algorithm` headers for
+    /// this command's requested checksum algorithm, if any.
+    fn checksum_headers(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Command::PutObject {
+                content,
+                checksum_algorithm: Some(alg),
+                ..
+            }
+            | Command::UploadPart {
+                content,
+                checksum_algorithm: Some(alg),
+                ..
+            } => alg.headers(content),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The base64 checksum of this part's content under its requested
+    /// algorithm, surfaced so the caller can record it and include it in
+    /// the `CompleteMultipartUpload` part list via [`Command::part_checksum_element`].
+    pub fn part_checksum(&self) -> Option<(ChecksumAlgorithm, String)> {
+        match self {
+            Command::UploadPart {
+                content,
+                checksum_algorithm: Some(alg),
+                ..
+            } => Some((*alg, alg.digest_b64(content))),
+            _ => None,
+        }
+    }
+
+    /// [`Command::part_checksum`], wrapped as the [`PartChecksum`] a
+    /// `CompletedPart` attaches so it serializes as the matching
+    /// `<ChecksumCRC32C>`-style element in the `CompleteMultipartUpload`
+    /// body.
+    pub fn part_checksum_element(&self) -> Option<PartChecksum> {
+        self.part_checksum()
+            .map(|(algorithm, digest_b64)| PartChecksum::new(algorithm, digest_b64))
+    }
+
+    /// Starts the running checksum for a [`Command::PutObjectStream`]
+    /// body, if a checksum algorithm was requested. The caller passes it to
+    /// each [`Command::next_signed_chunk`] call, which feeds the raw chunk
+    /// through [`ChecksumState::update`] as it's framed, then calls
+    /// [`ChecksumState::finish_b64`] once the stream ends — unlike
+    /// `PutObject`/`UploadPart`, there's no buffered `content` here for
+    /// `checksum_headers` to hash in one pass.
+    pub fn checksum_state(&self) -> Option<ChecksumState> {
+        match self {
+            Command::PutObjectStream {
+                checksum_algorithm: Some(alg),
+                ..
+            } => Some(alg.new_state()),
+            _ => None,
+        }
+    }
+
+    /// The `x-amz-decoded-content-length` header required alongside the
+    /// [`STREAMING_PAYLOAD_SHA`] sentinel returned by [`Command::sha256`]
+    /// for a chunked-signed upload.
+    fn decoded_content_length_header(&self) -> Option<(&'static str, String)> {
+        match self {
+            Command::PutObjectStream {
+                decoded_content_length,
+                ..
+            } => Some((
+                "x-amz-decoded-content-length",
+                decoded_content_length.to_string(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Reads, checksums, and signs the next chunk of a
+    /// [`Command::PutObjectStream`] body, framing it with [`frame_chunk`]
+    /// and signing it with [`next_chunk_signature`]. `checksum`, if given,
+    /// is updated with the raw (unframed) chunk bytes. Returns `None` once
+    /// the stream is exhausted — the caller still owes the backend the
+    /// standard zero-length final chunk, `frame_chunk(&[], &signature)`
+    /// signed the same way over an empty slice. Not meaningful for any
+    /// other `Command` variant.
+    pub async fn next_signed_chunk(
+        &mut self,
+        checksum: Option<&mut ChecksumState>,
+        signing_key: &[u8],
+        date: &str,
+        scope: &str,
+        previous_signature: &str,
+    ) -> Result<Option<(Vec<u8>, String)>, S3Error> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+        let Command::PutObjectStream { body, .. } = self else {
+            return Ok(None);
+        };
+        let mut chunk = vec![0u8; CHUNK_SIZE];
+        let mut filled = 0;
+        while filled < chunk.len() {
+            let read = body.0.as_mut().read(&mut chunk[filled..]).await?;
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+        if filled == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(filled);
+        if let Some(state) = checksum {
+            state.update(&chunk);
+        }
+        let signature = next_chunk_signature(signing_key, date, scope, previous_signature, &chunk);
+        Ok(Some((frame_chunk(&chunk, &signature), signature)))
+    }
 }
@@ -0,0 +1,144 @@
+//! XML request/response bodies for operations `command.rs` doesn't frame as
+//! raw bytes itself: multipart completion, batch delete, and bucket
+//! configuration (lifecycle/CORS). Serialized with `quick_xml`, the same
+//! approach `command.rs` uses for its own inline `DeleteXml`.
+
+use serde::{Deserialize, Serialize};
+
+/// One object to remove in a [`crate::command::Command::DeleteObjects`]
+/// batch request.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ObjectIdentifier {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub version_id: Option<String>,
+}
+
+/// A part's checksum, tagged by algorithm so it serializes under the
+/// element name S3 expects back for whichever algorithm the part was
+/// uploaded with (`<ChecksumCRC32>`, `<ChecksumCRC32C>`, `<ChecksumSHA1>`,
+/// `<ChecksumSHA256>`). Built from [`crate::command::Command::part_checksum`]
+/// via [`PartChecksum::new`].
+#[derive(Clone, Debug, Serialize)]
+pub enum PartChecksum {
+    #[serde(rename = "ChecksumCRC32")]
+    Crc32(String),
+    #[serde(rename = "ChecksumCRC32C")]
+    Crc32c(String),
+    #[serde(rename = "ChecksumSHA1")]
+    Sha1(String),
+    #[serde(rename = "ChecksumSHA256")]
+    Sha256(String),
+}
+
+impl PartChecksum {
+    pub fn new(algorithm: crate::command::ChecksumAlgorithm, digest_b64: String) -> Self {
+        match algorithm {
+            crate::command::ChecksumAlgorithm::Crc32 => Self::Crc32(digest_b64),
+            crate::command::ChecksumAlgorithm::Crc32c => Self::Crc32c(digest_b64),
+            crate::command::ChecksumAlgorithm::Sha1 => Self::Sha1(digest_b64),
+            crate::command::ChecksumAlgorithm::Sha256 => Self::Sha256(digest_b64),
+        }
+    }
+}
+
+/// One already-uploaded part, referenced by its `ETag`, in a
+/// `CompleteMultipartUpload` request body. `checksum` is only present when
+/// the part was uploaded with a checksum algorithm, and serializes
+/// alongside `ETag`/`PartNumber` as its own `<ChecksumCRC32C>`-style
+/// element.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CompletedPart {
+    pub part_number: u32,
+    pub e_tag: String,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<PartChecksum>,
+}
+
+impl CompletedPart {
+    pub fn new(part_number: u32, e_tag: String) -> Self {
+        CompletedPart {
+            part_number,
+            e_tag,
+            checksum: None,
+        }
+    }
+
+    pub fn with_checksum(mut self, checksum: Option<PartChecksum>) -> Self {
+        self.checksum = checksum;
+        self
+    }
+}
+
+/// Body of a `POST ?uploadId=` `CompleteMultipartUpload` request: the full
+/// ordered list of parts, each identified by its `ETag` and, when
+/// requested, its per-part checksum.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename = "CompleteMultipartUpload")]
+pub struct CompleteMultipartUploadData {
+    #[serde(rename = "Part")]
+    pub parts: Vec<CompletedPart>,
+}
+
+impl CompleteMultipartUploadData {
+    pub fn new(parts: Vec<CompletedPart>) -> Self {
+        CompleteMultipartUploadData { parts }
+    }
+
+    pub fn len(&self) -> usize {
+        self.to_string().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+impl std::fmt::Display for CompleteMultipartUploadData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            quick_xml::se::to_string(self).map_err(|_| std::fmt::Error)?
+        )
+    }
+}
+
+/// Body of a `PUT ?lifecycle` request.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename = "LifecycleConfiguration")]
+pub struct BucketLifecycleConfiguration {
+    #[serde(rename = "Rule")]
+    pub rules: Vec<LifecycleRule>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleRule {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub prefix: Option<String>,
+}
+
+/// Body of a `PUT ?cors` request.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename = "CORSConfiguration")]
+pub struct CorsConfiguration {
+    #[serde(rename = "CORSRule")]
+    pub rules: Vec<CorsRule>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CorsRule {
+    #[serde(rename = "AllowedMethod")]
+    pub allowed_methods: Vec<String>,
+    #[serde(rename = "AllowedOrigin")]
+    pub allowed_origins: Vec<String>,
+    #[serde(rename = "AllowedHeader", skip_serializing_if = "Option::is_none", default)]
+    pub allowed_headers: Option<Vec<String>>,
+}